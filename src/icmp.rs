@@ -16,6 +16,7 @@ use cast::usize;
 
 use fmt::Hex;
 use ipv4;
+use ipv4::{Checksum, ChecksumCapabilities};
 use {Invalid, Resize, Unknown, Valid};
 
 /* Packet structure */
@@ -53,6 +54,59 @@ pub unsafe trait Echo {}
 unsafe impl Echo for EchoReply {}
 unsafe impl Echo for EchoRequest {}
 
+/// [Type State] The Destination Unreachable type
+pub enum DestinationUnreachable {}
+
+/// [Implementation Detail] marker for type states whose `code` field is a plain `u8`
+///
+/// Types like `DestinationUnreachable` interpret their `code` field as a richer enum, so they
+/// are not covered by this trait and instead provide their own `get_code`.
+#[doc(hidden)]
+pub unsafe trait RawCode {}
+
+unsafe impl RawCode for Unknown {}
+unsafe impl RawCode for EchoReply {}
+unsafe impl RawCode for EchoRequest {}
+
+/// [Type State] The Timestamp type
+pub enum Timestamp {}
+
+/// [Type State] The Timestamp Reply type
+pub enum TimestampReply {}
+
+/// [Implementation Detail] Timestamp or TimestampReply
+#[doc(hidden)]
+pub unsafe trait TimestampMessage {}
+
+unsafe impl TimestampMessage for Timestamp {}
+unsafe impl TimestampMessage for TimestampReply {}
+
+unsafe impl RawCode for Timestamp {}
+unsafe impl RawCode for TimestampReply {}
+
+/// [Type State] The Time Exceeded type
+pub enum TimeExceeded {}
+
+/// [Type State] The Parameter Problem type
+pub enum ParameterProblem {}
+
+/// [Implementation Detail] marker for type states whose trailing bytes are addressable through
+/// `payload` / `payload_mut`
+///
+/// `Timestamp` and `TimestampReply` don't implement this: their header occupies the packet's
+/// full, fixed length (RFC 792 defines no trailing data for them), so the module-level `PAYLOAD`
+/// range (which starts right after the 8-byte Echo-style header) would alias their Originate /
+/// Receive / Transmit Timestamp fields.
+#[doc(hidden)]
+pub unsafe trait HasPayload {}
+
+unsafe impl HasPayload for Unknown {}
+unsafe impl HasPayload for EchoReply {}
+unsafe impl HasPayload for EchoRequest {}
+unsafe impl HasPayload for DestinationUnreachable {}
+unsafe impl HasPayload for TimeExceeded {}
+unsafe impl HasPayload for ParameterProblem {}
+
 /* EchoRequest */
 impl<B> Packet<B, EchoRequest, Invalid>
 where
@@ -107,6 +161,227 @@ where
     }
 }
 
+/* Timestamp */
+// bytes 4..8 are the (shared) IDENT / SEQ_NO fields; these 3 fields are specific to this message
+const ORIGINATE_TS: Range<usize> = 8..12;
+const RECEIVE_TS: Range<usize> = 12..16;
+const TRANSMIT_TS: Range<usize> = 16..20;
+
+/// Size of a Timestamp (or Timestamp Reply) ICMP header
+const TS_HEADER_SIZE: u16 = TRANSMIT_TS.end as u16;
+
+impl<B> Packet<B, Timestamp, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]> + Resize,
+{
+    /* Constructors */
+    /// Transforms the input buffer into a Timestamp ICMP packet
+    pub fn new(buffer: B) -> Self {
+        assert!(buffer.as_ref().len() >= usize(TS_HEADER_SIZE));
+
+        let mut packet: Packet<B, Unknown, Invalid> = unsafe { Packet::unchecked(buffer) };
+
+        packet.set_type(Type::Timestamp);
+        packet.set_code(0);
+
+        unsafe { Packet::unchecked(packet.buffer) }
+    }
+}
+
+/* Timestamp OR TimestampReply */
+// NOTE these can't be collapsed into a single `T: TimestampMessage` impl, the way the
+// originate/receive/transmit timestamp accessors below are: that would conflict with the
+// `E: Echo` impl above, since two generic impls bounded by different marker traits aren't
+// allowed to define a method of the same name, even when the traits themselves are disjoint.
+// The per-type impls below stay split for that reason, but share their bodies through these
+// helpers instead of duplicating them.
+fn read_identifier(bytes: &[u8]) -> u16 {
+    NE::read_u16(&bytes[IDENT])
+}
+
+fn read_sequence_number(bytes: &[u8]) -> u16 {
+    NE::read_u16(&bytes[SEQ_NO])
+}
+
+fn write_identifier(bytes: &mut [u8], ident: u16) {
+    NE::write_u16(&mut bytes[IDENT], ident)
+}
+
+fn write_sequence_number(bytes: &mut [u8], seq_no: u16) {
+    NE::write_u16(&mut bytes[SEQ_NO], seq_no)
+}
+
+impl<B> Packet<B, Timestamp, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /* Setters */
+    /// Sets the Identifier field of the header
+    pub fn set_identifier(&mut self, ident: u16) {
+        write_identifier(self.as_mut(), ident)
+    }
+
+    /// Sets the Sequence Number field of the header
+    pub fn set_sequence_number(&mut self, seq_no: u16) {
+        write_sequence_number(self.as_mut(), seq_no)
+    }
+}
+
+impl<B> Packet<B, TimestampReply, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /* Setters */
+    /// Sets the Identifier field of the header
+    pub fn set_identifier(&mut self, ident: u16) {
+        write_identifier(self.as_mut(), ident)
+    }
+
+    /// Sets the Sequence Number field of the header
+    pub fn set_sequence_number(&mut self, seq_no: u16) {
+        write_sequence_number(self.as_mut(), seq_no)
+    }
+}
+
+impl<B, C> Packet<B, Timestamp, C>
+where
+    B: AsRef<[u8]>,
+{
+    /* Getters */
+    /// Returns the Identifier field of the header
+    pub fn get_identifier(&self) -> u16 {
+        read_identifier(self.as_ref())
+    }
+
+    /// Returns the Sequence Number field of the header
+    pub fn get_sequence_number(&self) -> u16 {
+        read_sequence_number(self.as_ref())
+    }
+}
+
+impl<B, C> Packet<B, TimestampReply, C>
+where
+    B: AsRef<[u8]>,
+{
+    /* Getters */
+    /// Returns the Identifier field of the header
+    pub fn get_identifier(&self) -> u16 {
+        read_identifier(self.as_ref())
+    }
+
+    /// Returns the Sequence Number field of the header
+    pub fn get_sequence_number(&self) -> u16 {
+        read_sequence_number(self.as_ref())
+    }
+}
+
+impl<B, T, C> Packet<B, T, C>
+where
+    B: AsRef<[u8]>,
+    T: TimestampMessage,
+{
+    /// Returns the Originate Timestamp field of the header
+    ///
+    /// The value is the number of milliseconds since midnight UT
+    pub fn get_originate_timestamp(&self) -> u32 {
+        NE::read_u32(&self.as_ref()[ORIGINATE_TS])
+    }
+
+    /// Returns the Receive Timestamp field of the header
+    ///
+    /// The value is the number of milliseconds since midnight UT
+    pub fn get_receive_timestamp(&self) -> u32 {
+        NE::read_u32(&self.as_ref()[RECEIVE_TS])
+    }
+
+    /// Returns the Transmit Timestamp field of the header
+    ///
+    /// The value is the number of milliseconds since midnight UT
+    pub fn get_transmit_timestamp(&self) -> u32 {
+        NE::read_u32(&self.as_ref()[TRANSMIT_TS])
+    }
+}
+
+impl<B, T> Packet<B, T, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+    T: TimestampMessage,
+{
+    /// Sets the Originate Timestamp field of the header
+    ///
+    /// The value must be the number of milliseconds since midnight UT
+    pub fn set_originate_timestamp(&mut self, ms: u32) {
+        NE::write_u32(&mut self.as_mut()[ORIGINATE_TS], ms)
+    }
+
+    /// Sets the Receive Timestamp field of the header
+    ///
+    /// The value must be the number of milliseconds since midnight UT
+    pub fn set_receive_timestamp(&mut self, ms: u32) {
+        NE::write_u32(&mut self.as_mut()[RECEIVE_TS], ms)
+    }
+
+    /// Sets the Transmit Timestamp field of the header
+    ///
+    /// The value must be the number of milliseconds since midnight UT
+    pub fn set_transmit_timestamp(&mut self, ms: u32) {
+        NE::write_u32(&mut self.as_mut()[TRANSMIT_TS], ms)
+    }
+}
+
+impl<B, C> From<Packet<B, Timestamp, C>> for Packet<B, TimestampReply, Valid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn from(p: Packet<B, Timestamp, C>) -> Self {
+        let mut p: Packet<B, Unknown, Invalid> = unsafe { Packet::unchecked(p.buffer) };
+        p.set_type(Type::TimestampReply);
+        // NOTE the Originate Timestamp occupies the same bytes in both type states, so it
+        // carries over to the reply without any extra work
+        let p: Packet<B, TimestampReply, Invalid> = unsafe { Packet::unchecked(p.buffer) };
+        p.update_checksum()
+    }
+}
+
+impl<B, C> TryFrom<Packet<B, Unknown, C>> for Packet<B, Timestamp, C>
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Packet<B, Unknown, C>;
+
+    fn try_from(p: Packet<B, Unknown, C>) -> Result<Self, Packet<B, Unknown, C>> {
+        // NOTE `Packet<B, Unknown, _>::parse` only guarantees `len >= HEADER_SIZE` (8 bytes), but
+        // the Timestamp fields extend out to `TS_HEADER_SIZE` (20 bytes); without this check a
+        // truncated packet downcasts successfully and then panics on the first timestamp access
+        if p.get_type() == Type::Timestamp
+            && p.get_code() == 0
+            && p.as_ref().len() >= usize(TS_HEADER_SIZE)
+        {
+            Ok(unsafe { Packet::unchecked(p.buffer) })
+        } else {
+            Err(p)
+        }
+    }
+}
+
+impl<B, C> TryFrom<Packet<B, Unknown, C>> for Packet<B, TimestampReply, C>
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Packet<B, Unknown, C>;
+
+    fn try_from(p: Packet<B, Unknown, C>) -> Result<Self, Packet<B, Unknown, C>> {
+        if p.get_type() == Type::TimestampReply
+            && p.get_code() == 0
+            && p.as_ref().len() >= usize(TS_HEADER_SIZE)
+        {
+            Ok(unsafe { Packet::unchecked(p.buffer) })
+        } else {
+            Err(p)
+        }
+    }
+}
+
 /* Unknown */
 impl<B> Packet<B, Unknown, Valid>
 where
@@ -115,13 +390,18 @@ where
     /* Constructors */
     /// Parses the input bytes into a
     pub fn parse(bytes: B) -> Result<Self, B> {
+        Self::parse_with_caps(bytes, &ChecksumCapabilities::default())
+    }
+
+    /// Parses the input bytes into a, applying the given checksum capabilities
+    pub fn parse_with_caps(bytes: B, caps: &ChecksumCapabilities) -> Result<Self, B> {
         if bytes.as_ref().len() < usize(HEADER_SIZE) {
             return Err(bytes);
         }
 
         let packet: Self = unsafe { Packet::unchecked(bytes) };
 
-        if ipv4::verify_checksum(packet.as_bytes()) {
+        if !caps.icmp.verify() || ipv4::verify_checksum(packet.as_bytes()) {
             Ok(packet)
         } else {
             Err(packet.buffer)
@@ -220,6 +500,131 @@ where
     }
 }
 
+/* DestinationUnreachable */
+// bytes 4..8 are unused, except for the FragRequired code, where bytes 6..8 hold the next-hop MTU
+const NEXT_HOP_MTU: Range<usize> = 6..8;
+
+impl<B, C> Packet<B, DestinationUnreachable, C>
+where
+    B: AsRef<[u8]>,
+{
+    /* Getters */
+    /// Returns the Code field of the header
+    pub fn get_code(&self) -> Code {
+        self.as_ref()[CODE].into()
+    }
+
+    /// Returns the Next-Hop MTU field of the header
+    ///
+    /// NOTE this is only meaningful when `get_code()` is `Code::FragRequired`
+    pub fn next_hop_mtu(&self) -> u16 {
+        NE::read_u16(&self.as_ref()[NEXT_HOP_MTU])
+    }
+
+    /// Parses the IP header (and first 8 bytes of the payload) of the datagram that triggered
+    /// this message
+    pub fn parse_offending_datagram(&self) -> Result<ipv4::Packet<&[u8], Valid>, &[u8]> {
+        ipv4::Packet::parse(self.payload())
+    }
+}
+
+impl<B, C> TryFrom<Packet<B, Unknown, C>> for Packet<B, DestinationUnreachable, C>
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Packet<B, Unknown, C>;
+
+    fn try_from(p: Packet<B, Unknown, C>) -> Result<Self, Packet<B, Unknown, C>> {
+        if p.get_type() == Type::DestinationUnreachable {
+            Ok(unsafe { Packet::unchecked(p.buffer) })
+        } else {
+            Err(p)
+        }
+    }
+}
+
+/* TimeExceeded */
+impl<B, C> Packet<B, TimeExceeded, C>
+where
+    B: AsRef<[u8]>,
+{
+    /* Getters */
+    /// Returns the Code field of the header
+    pub fn get_code(&self) -> TimeExceededCode {
+        self.as_ref()[CODE].into()
+    }
+
+    /// Parses the IP header (and first 8 bytes of the payload) of the datagram that triggered
+    /// this message
+    pub fn parse_offending_datagram(&self) -> Result<ipv4::Packet<&[u8], Valid>, &[u8]> {
+        ipv4::Packet::parse(self.payload())
+    }
+}
+
+impl<B, C> TryFrom<Packet<B, Unknown, C>> for Packet<B, TimeExceeded, C>
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Packet<B, Unknown, C>;
+
+    fn try_from(p: Packet<B, Unknown, C>) -> Result<Self, Packet<B, Unknown, C>> {
+        if p.get_type() == Type::TimeExceeded {
+            Ok(unsafe { Packet::unchecked(p.buffer) })
+        } else {
+            Err(p)
+        }
+    }
+}
+
+/* ParameterProblem */
+// byte 4 is the Pointer field; bytes 5..8 are unused
+const POINTER: usize = 4;
+
+impl<B, C> Packet<B, ParameterProblem, C>
+where
+    B: AsRef<[u8]>,
+{
+    /* Getters */
+    /// Returns the Pointer field of the header
+    ///
+    /// This points at the byte, within the offending datagram, that caused the problem
+    pub fn get_pointer(&self) -> u8 {
+        self.as_ref()[POINTER]
+    }
+
+    /// Parses the IP header (and first 8 bytes of the payload) of the datagram that triggered
+    /// this message
+    pub fn parse_offending_datagram(&self) -> Result<ipv4::Packet<&[u8], Valid>, &[u8]> {
+        ipv4::Packet::parse(self.payload())
+    }
+}
+
+impl<B> Packet<B, ParameterProblem, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /* Setters */
+    /// Sets the Pointer field of the header
+    pub fn set_pointer(&mut self, pointer: u8) {
+        self.as_mut()[POINTER] = pointer;
+    }
+}
+
+impl<B, C> TryFrom<Packet<B, Unknown, C>> for Packet<B, ParameterProblem, C>
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Packet<B, Unknown, C>;
+
+    fn try_from(p: Packet<B, Unknown, C>) -> Result<Self, Packet<B, Unknown, C>> {
+        if p.get_type() == Type::ParameterProblem {
+            Ok(unsafe { Packet::unchecked(p.buffer) })
+        } else {
+            Err(p)
+        }
+    }
+}
+
 /* TYPE */
 impl<B, T, C> Packet<B, T, C>
 where
@@ -241,27 +646,15 @@ where
             Type::EchoReply
         } else if typeid!(T == EchoRequest) {
             Type::EchoRequest
+        } else if typeid!(T == Timestamp) {
+            Type::Timestamp
+        } else if typeid!(T == TimestampReply) {
+            Type::TimestampReply
         } else {
             self.as_ref()[TYPE].into()
         }
     }
 
-    /// Returns the Type field of the header
-    pub fn get_code(&self) -> u8 {
-        if typeid!(T == EchoReply) {
-            0
-        } else if typeid!(T == EchoRequest) {
-            0
-        } else {
-            self.as_ref()[CODE]
-        }
-    }
-
-    /// View into the payload
-    pub fn payload(&self) -> &[u8] {
-        &self.as_ref()[PAYLOAD]
-    }
-
     /// Returns the length (header + data) of this packet
     pub fn len(&self) -> u16 {
         self.as_ref().len() as u16
@@ -282,6 +675,38 @@ where
     }
 }
 
+impl<B, T, C> Packet<B, T, C>
+where
+    B: AsRef<[u8]>,
+    T: HasPayload,
+{
+    /// View into the payload
+    pub fn payload(&self) -> &[u8] {
+        &self.as_ref()[PAYLOAD]
+    }
+}
+
+impl<B, T, C> Packet<B, T, C>
+where
+    B: AsRef<[u8]>,
+    T: RawCode,
+{
+    /// Returns the Code field of the header
+    pub fn get_code(&self) -> u8 {
+        if typeid!(T == EchoReply) {
+            0
+        } else if typeid!(T == EchoRequest) {
+            0
+        } else if typeid!(T == Timestamp) {
+            0
+        } else if typeid!(T == TimestampReply) {
+            0
+        } else {
+            self.as_ref()[CODE]
+        }
+    }
+}
+
 impl<B, T, C> Packet<B, T, C>
 where
     B: AsRef<[u8]> + AsMut<[u8]>,
@@ -295,16 +720,31 @@ where
 impl<B, T> Packet<B, T, Invalid>
 where
     B: AsRef<[u8]> + AsMut<[u8]>,
+    T: HasPayload,
 {
     /// Mutable view into the payload
     pub fn payload_mut(&mut self) -> &mut [u8] {
         &mut self.as_mut()[PAYLOAD]
     }
+}
 
+impl<B, T> Packet<B, T, Invalid>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
     /// Updates the Checksum field of the header
-    pub fn update_checksum(mut self) -> Packet<B, T, Valid> {
-        let cksum = ipv4::compute_checksum(&self.as_bytes(), CHECKSUM.start);
-        NE::write_u16(&mut self.as_mut()[CHECKSUM], cksum);
+    pub fn update_checksum(self) -> Packet<B, T, Valid> {
+        self.update_checksum_with_caps(&ChecksumCapabilities::default())
+    }
+
+    /// Updates the Checksum field of the header, applying the given checksum capabilities
+    pub fn update_checksum_with_caps(mut self, caps: &ChecksumCapabilities) -> Packet<B, T, Valid> {
+        if caps.icmp.compute() {
+            let cksum = ipv4::compute_checksum(&self.as_bytes(), CHECKSUM.start);
+            NE::write_u16(&mut self.as_mut()[CHECKSUM], cksum);
+        } else {
+            NE::write_u16(&mut self.as_mut()[CHECKSUM], 0);
+        }
 
         unsafe { Packet::unchecked(self.buffer) }
     }
@@ -374,15 +814,267 @@ pub enum Type {
     DestinationUnreachable = 3,
     /// Echo Request
     EchoRequest = 8,
+    /// Time Exceeded
+    TimeExceeded = 11,
+    /// Parameter Problem
+    ParameterProblem = 12,
+    /// Timestamp
+    Timestamp = 13,
+    /// Timestamp Reply
+    TimestampReply = 14,
 }
 );
 
+full_range!(u8,
+/// Destination Unreachable codes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Code {
+    /// Network Unreachable
+    NetUnreachable = 0,
+    /// Host Unreachable
+    HostUnreachable = 1,
+    /// Protocol Unreachable
+    ProtoUnreachable = 2,
+    /// Port Unreachable
+    PortUnreachable = 3,
+    /// Fragmentation Required (and DF was set)
+    FragRequired = 4,
+    /// Source Route Failed
+    SrcRouteFailed = 5,
+    /// Destination Network Unknown
+    DstNetUnknown = 6,
+    /// Destination Host Unknown
+    DstHostUnknown = 7,
+    /// Source Host Isolated
+    SrcHostIsolated = 8,
+    /// Network Administratively Prohibited
+    NetProhibited = 9,
+    /// Host Administratively Prohibited
+    HostProhibited = 10,
+}
+);
+
+full_range!(u8,
+/// Time Exceeded codes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeExceededCode {
+    /// Time to Live exceeded in Transit
+    TtlExpired = 0,
+    /// Fragment Reassembly Time Exceeded
+    FragReassemblyExceeded = 1,
+}
+);
+
+/// A parsed ICMP message
+///
+/// Unlike `Packet`, this is a single, self-contained value: no type-state transitions are
+/// needed to inspect or build one. It borrows its payload (and, for `DstUnreachable`, the
+/// offending datagram) from the buffer it was parsed out of.
+#[derive(Debug)]
+pub enum Repr<'a> {
+    /// Echo Request
+    EchoRequest {
+        /// Identifier
+        id: u16,
+        /// Sequence Number
+        seq: u16,
+        /// Payload
+        payload: &'a [u8],
+    },
+    /// Echo Reply
+    EchoReply {
+        /// Identifier
+        id: u16,
+        /// Sequence Number
+        seq: u16,
+        /// Payload
+        payload: &'a [u8],
+    },
+    /// Destination Unreachable
+    DstUnreachable {
+        /// Reason why the datagram was dropped
+        reason: Code,
+        /// Next-Hop MTU; only meaningful when `reason` is `Code::FragRequired`
+        next_hop_mtu: u16,
+        /// IP header (and first 8 bytes) of the datagram that triggered this message
+        header: ipv4::Packet<&'a [u8], Valid>,
+    },
+    /// Timestamp
+    Timestamp {
+        /// Identifier
+        id: u16,
+        /// Sequence Number
+        seq: u16,
+        /// Originate Timestamp
+        originate_ts: u32,
+        /// Receive Timestamp
+        receive_ts: u32,
+        /// Transmit Timestamp
+        transmit_ts: u32,
+    },
+    /// Timestamp Reply
+    TimestampReply {
+        /// Identifier
+        id: u16,
+        /// Sequence Number
+        seq: u16,
+        /// Originate Timestamp
+        originate_ts: u32,
+        /// Receive Timestamp
+        receive_ts: u32,
+        /// Transmit Timestamp
+        transmit_ts: u32,
+    },
+}
+
+impl<'a> Repr<'a> {
+    /// Parses the given ICMP packet into a `Repr`
+    pub fn parse<B>(packet: &'a Packet<B, Unknown, Valid>) -> Result<Self, Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        match (packet.get_type(), packet.get_code()) {
+            (Type::EchoRequest, 0) => Ok(Repr::EchoRequest {
+                id: NE::read_u16(&packet.as_bytes()[IDENT]),
+                seq: NE::read_u16(&packet.as_bytes()[SEQ_NO]),
+                payload: &packet.as_bytes()[PAYLOAD],
+            }),
+            (Type::EchoReply, 0) => Ok(Repr::EchoReply {
+                id: NE::read_u16(&packet.as_bytes()[IDENT]),
+                seq: NE::read_u16(&packet.as_bytes()[SEQ_NO]),
+                payload: &packet.as_bytes()[PAYLOAD],
+            }),
+            (Type::DestinationUnreachable, code) => Ok(Repr::DstUnreachable {
+                reason: code.into(),
+                next_hop_mtu: NE::read_u16(&packet.as_bytes()[NEXT_HOP_MTU]),
+                header: ipv4::Packet::parse(&packet.as_bytes()[PAYLOAD])
+                    .map_err(|_| Error::Malformed)?,
+            }),
+            // NOTE `packet` only guarantees `len >= HEADER_SIZE` (8 bytes); the Timestamp fields
+            // extend out to `TS_HEADER_SIZE` (20 bytes), so a truncated packet must be rejected
+            // here rather than indexed out of bounds
+            (Type::Timestamp, 0) if packet.as_bytes().len() >= usize(TS_HEADER_SIZE) => {
+                Ok(Repr::Timestamp {
+                    id: NE::read_u16(&packet.as_bytes()[IDENT]),
+                    seq: NE::read_u16(&packet.as_bytes()[SEQ_NO]),
+                    originate_ts: NE::read_u32(&packet.as_bytes()[ORIGINATE_TS]),
+                    receive_ts: NE::read_u32(&packet.as_bytes()[RECEIVE_TS]),
+                    transmit_ts: NE::read_u32(&packet.as_bytes()[TRANSMIT_TS]),
+                })
+            }
+            (Type::TimestampReply, 0) if packet.as_bytes().len() >= usize(TS_HEADER_SIZE) => {
+                Ok(Repr::TimestampReply {
+                    id: NE::read_u16(&packet.as_bytes()[IDENT]),
+                    seq: NE::read_u16(&packet.as_bytes()[SEQ_NO]),
+                    originate_ts: NE::read_u32(&packet.as_bytes()[ORIGINATE_TS]),
+                    receive_ts: NE::read_u32(&packet.as_bytes()[RECEIVE_TS]),
+                    transmit_ts: NE::read_u32(&packet.as_bytes()[TRANSMIT_TS]),
+                })
+            }
+            (Type::Timestamp, 0) | (Type::TimestampReply, 0) => Err(Error::Malformed),
+            _ => Err(Error::Unrecognized),
+        }
+    }
+
+    /// Returns the number of bytes needed to `emit` this `Repr`
+    pub fn buffer_len(&self) -> usize {
+        match *self {
+            Repr::EchoRequest { payload, .. } | Repr::EchoReply { payload, .. } => {
+                usize(HEADER_SIZE) + payload.len()
+            }
+            Repr::DstUnreachable { ref header, .. } => usize(HEADER_SIZE) + usize(header.len()),
+            Repr::Timestamp { .. } | Repr::TimestampReply { .. } => usize(TS_HEADER_SIZE),
+        }
+    }
+
+    /// Emits this `Repr` into `packet`
+    ///
+    /// NOTE this does not compute the checksum; call `update_checksum` on `packet` afterwards
+    pub fn emit<B>(&self, packet: &mut Packet<B, Unknown, Invalid>)
+    where
+        B: AsRef<[u8]> + AsMut<[u8]>,
+    {
+        match *self {
+            Repr::EchoRequest { id, seq, payload } => {
+                packet.set_type(Type::EchoRequest);
+                packet.set_code(0);
+                NE::write_u16(&mut packet.as_mut()[IDENT], id);
+                NE::write_u16(&mut packet.as_mut()[SEQ_NO], seq);
+                packet.payload_mut()[..payload.len()].copy_from_slice(payload);
+            }
+            Repr::EchoReply { id, seq, payload } => {
+                packet.set_type(Type::EchoReply);
+                packet.set_code(0);
+                NE::write_u16(&mut packet.as_mut()[IDENT], id);
+                NE::write_u16(&mut packet.as_mut()[SEQ_NO], seq);
+                packet.payload_mut()[..payload.len()].copy_from_slice(payload);
+            }
+            Repr::DstUnreachable {
+                reason,
+                next_hop_mtu,
+                ref header,
+            } => {
+                packet.set_type(Type::DestinationUnreachable);
+                packet.set_code(reason.into());
+                let mtu = if reason == Code::FragRequired {
+                    next_hop_mtu
+                } else {
+                    0
+                };
+                NE::write_u16(&mut packet.as_mut()[NEXT_HOP_MTU], mtu);
+                let len = usize(header.len());
+                packet.payload_mut()[..len].copy_from_slice(header.as_bytes());
+            }
+            Repr::Timestamp {
+                id,
+                seq,
+                originate_ts,
+                receive_ts,
+                transmit_ts,
+            } => {
+                packet.set_type(Type::Timestamp);
+                packet.set_code(0);
+                NE::write_u16(&mut packet.as_mut()[IDENT], id);
+                NE::write_u16(&mut packet.as_mut()[SEQ_NO], seq);
+                NE::write_u32(&mut packet.as_mut()[ORIGINATE_TS], originate_ts);
+                NE::write_u32(&mut packet.as_mut()[RECEIVE_TS], receive_ts);
+                NE::write_u32(&mut packet.as_mut()[TRANSMIT_TS], transmit_ts);
+            }
+            Repr::TimestampReply {
+                id,
+                seq,
+                originate_ts,
+                receive_ts,
+                transmit_ts,
+            } => {
+                packet.set_type(Type::TimestampReply);
+                packet.set_code(0);
+                NE::write_u16(&mut packet.as_mut()[IDENT], id);
+                NE::write_u16(&mut packet.as_mut()[SEQ_NO], seq);
+                NE::write_u32(&mut packet.as_mut()[ORIGINATE_TS], originate_ts);
+                NE::write_u32(&mut packet.as_mut()[RECEIVE_TS], receive_ts);
+                NE::write_u32(&mut packet.as_mut()[TRANSMIT_TS], transmit_ts);
+            }
+        }
+    }
+}
+
+/// Errors that can occur while parsing a `Repr` out of a `Packet`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// The packet is too short for its type, or the embedded IPv4 header failed to parse
+    Malformed,
+    /// This (type, code) combination is not supported by `Repr`
+    Unrecognized,
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{self, Rng};
 
     use {ether, icmp, mac, ipv4};
     use Buffer;
+    use super::{Invalid, Unknown};
 
     const SIZE: usize = 42;
 
@@ -455,4 +1147,282 @@ mod tests {
         assert_eq!(icmp.get_identifier(), 4);
         assert_eq!(icmp.get_sequence_number(), 2);
     }
+
+    #[test]
+    fn destination_unreachable() {
+        const BYTES: [u8; 36] = [
+            3, 3, 252, 252, // icmp: type, code, checksum
+            0, 0, 0, 0, // icmp: unused
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110, // ipv4: offending datagram's header
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, // offending datagram: first 8 bytes of payload
+        ];
+
+        let icmp = icmp::Packet::parse(&BYTES[..])
+            .unwrap()
+            .downcast::<icmp::DestinationUnreachable>()
+            .unwrap();
+
+        assert_eq!(icmp.get_code(), icmp::Code::PortUnreachable);
+
+        let offending = icmp.parse_offending_datagram().unwrap();
+        assert_eq!(offending.get_source(), IP_SRC);
+        assert_eq!(offending.get_destination(), IP_DST);
+    }
+
+    #[test]
+    fn destination_unreachable_frag_required() {
+        const BYTES: [u8; 36] = [
+            3, 4, 247, 131, // icmp: type, code, checksum
+            0, 0, 5, 120, // icmp: unused, next-hop MTU = 1400
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110, // ipv4: offending datagram's header
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, // offending datagram: first 8 bytes of payload
+        ];
+
+        let icmp = icmp::Packet::parse(&BYTES[..])
+            .unwrap()
+            .downcast::<icmp::DestinationUnreachable>()
+            .unwrap();
+
+        assert_eq!(icmp.get_code(), icmp::Code::FragRequired);
+        assert_eq!(icmp.next_hop_mtu(), 1400);
+    }
+
+    #[test]
+    fn time_exceeded() {
+        const BYTES: [u8; 36] = [
+            11, 0, 244, 255, // icmp: type, code, checksum
+            0, 0, 0, 0, // icmp: unused
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110, // ipv4: offending datagram's header
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, // offending datagram: first 8 bytes of payload
+        ];
+
+        let icmp = icmp::Packet::parse(&BYTES[..])
+            .unwrap()
+            .downcast::<icmp::TimeExceeded>()
+            .unwrap();
+
+        assert_eq!(icmp.get_code(), icmp::TimeExceededCode::TtlExpired);
+
+        let offending = icmp.parse_offending_datagram().unwrap();
+        assert_eq!(offending.get_source(), IP_SRC);
+    }
+
+    #[test]
+    fn parameter_problem() {
+        const BYTES: [u8; 36] = [
+            12, 0, 242, 255, // icmp: type, code, checksum
+            1, 0, 0, 0, // icmp: pointer, unused
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110, // ipv4: offending datagram's header
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, // offending datagram: first 8 bytes of payload
+        ];
+
+        let icmp = icmp::Packet::parse(&BYTES[..])
+            .unwrap()
+            .downcast::<icmp::ParameterProblem>()
+            .unwrap();
+
+        assert_eq!(icmp.get_pointer(), 1);
+
+        let offending = icmp.parse_offending_datagram().unwrap();
+        assert_eq!(offending.get_destination(), IP_DST);
+    }
+
+    #[test]
+    fn timestamp() {
+        const BYTES: [u8; 20] = [
+            13, 0, 239, 7, // icmp: type, code, checksum
+            0, 7, // icmp: identifier
+            0, 9, // icmp: sequence number
+            0, 0, 3, 232, // icmp: originate timestamp
+            0, 0, 0, 0, // icmp: receive timestamp
+            0, 0, 0, 0, // icmp: transmit timestamp
+        ];
+
+        let mut array: [u8; 20] = [0; 20];
+        let mut icmp: icmp::Packet<_, icmp::Timestamp, _> = icmp::Packet::new(Buffer::new(&mut array));
+
+        icmp.set_identifier(7);
+        icmp.set_sequence_number(9);
+        icmp.set_originate_timestamp(1000);
+        icmp.set_receive_timestamp(0);
+        icmp.set_transmit_timestamp(0);
+
+        let icmp = icmp.update_checksum();
+
+        assert_eq!(icmp.as_bytes(), &BYTES[..]);
+    }
+
+    #[test]
+    fn timestamp_reply() {
+        let mut array: [u8; 20] = [0; 20];
+        let mut icmp: icmp::Packet<_, icmp::Timestamp, _> = icmp::Packet::new(Buffer::new(&mut array));
+
+        icmp.set_identifier(7);
+        icmp.set_sequence_number(9);
+        icmp.set_originate_timestamp(1000);
+
+        let icmp = icmp.update_checksum();
+        let reply: icmp::Packet<_, icmp::TimestampReply, _> = icmp.into();
+
+        assert_eq!(reply.get_type(), icmp::Type::TimestampReply);
+        assert_eq!(reply.get_identifier(), 7);
+        assert_eq!(reply.get_sequence_number(), 9);
+        assert_eq!(reply.get_originate_timestamp(), 1000);
+    }
+
+    #[test]
+    fn repr_round_trip_echo_request() {
+        let packet = icmp::Packet::parse(&BYTES[34..]).unwrap();
+        let repr = icmp::Repr::parse(&packet).unwrap();
+
+        match repr {
+            icmp::Repr::EchoRequest { id, seq, payload } => {
+                assert_eq!(id, 4);
+                assert_eq!(seq, 2);
+                assert!(payload.is_empty());
+            }
+            _ => panic!("wrong Repr variant"),
+        }
+
+        let mut array = [0; 8];
+        let mut out: icmp::Packet<_, Unknown, Invalid> =
+            unsafe { icmp::Packet::unchecked(&mut array[..]) };
+
+        repr.emit(&mut out);
+        let out = out.update_checksum();
+
+        assert_eq!(out.as_bytes(), &BYTES[34..]);
+    }
+
+    #[test]
+    fn repr_round_trip_destination_unreachable() {
+        const BYTES: [u8; 36] = [
+            3, 3, 252, 252, 0, 0, 0, 0,
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110,
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        let packet = icmp::Packet::parse(&BYTES[..]).unwrap();
+        let repr = icmp::Repr::parse(&packet).unwrap();
+
+        let mut array = [0; 36];
+        let mut out: icmp::Packet<_, Unknown, Invalid> =
+            unsafe { icmp::Packet::unchecked(&mut array[..]) };
+
+        repr.emit(&mut out);
+        let out = out.update_checksum();
+
+        assert_eq!(out.as_bytes(), &BYTES[..]);
+    }
+
+    #[test]
+    fn repr_round_trip_destination_unreachable_frag_required() {
+        const BYTES: [u8; 36] = [
+            3, 4, 247, 131, 0, 0, 5, 120,
+            69, 0, 0, 28, 0, 0, 64, 0, 64, 1, 185, 110,
+            192, 168, 0, 33, 192, 168, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        let packet = icmp::Packet::parse(&BYTES[..]).unwrap();
+        let repr = icmp::Repr::parse(&packet).unwrap();
+
+        match repr {
+            icmp::Repr::DstUnreachable {
+                reason,
+                next_hop_mtu,
+                ..
+            } => {
+                assert_eq!(reason, icmp::Code::FragRequired);
+                assert_eq!(next_hop_mtu, 1400);
+            }
+            _ => panic!("wrong Repr variant"),
+        }
+
+        let mut array = [0; 36];
+        let mut out: icmp::Packet<_, Unknown, Invalid> =
+            unsafe { icmp::Packet::unchecked(&mut array[..]) };
+
+        repr.emit(&mut out);
+        let out = out.update_checksum();
+
+        // NOTE this is the part that regresses without the Next-Hop MTU fix: the MTU must survive
+        // the parse -> emit round trip
+        assert_eq!(out.as_bytes(), &BYTES[..]);
+    }
+
+    #[test]
+    fn repr_round_trip_timestamp() {
+        const BYTES: [u8; 20] = [
+            13, 0, 239, 7, 0, 7, 0, 9, 0, 0, 3, 232, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        let mut array: [u8; 20] = [0; 20];
+        let mut icmp: icmp::Packet<_, icmp::Timestamp, _> = icmp::Packet::new(Buffer::new(&mut array));
+        icmp.set_identifier(7);
+        icmp.set_sequence_number(9);
+        icmp.set_originate_timestamp(1000);
+        let icmp = icmp.update_checksum();
+
+        let packet: icmp::Packet<_, Unknown, _> = unsafe { icmp::Packet::unchecked(icmp.as_bytes()) };
+        let repr = icmp::Repr::parse(&packet).unwrap();
+
+        let mut out_array = [0; 20];
+        let mut out: icmp::Packet<_, Unknown, Invalid> =
+            unsafe { icmp::Packet::unchecked(&mut out_array[..]) };
+
+        repr.emit(&mut out);
+        let out = out.update_checksum();
+
+        assert_eq!(out.as_bytes(), &BYTES[..]);
+    }
+
+    #[test]
+    fn checksum_capabilities() {
+        // a packet with a deliberately wrong checksum
+        const BYTES: [u8; 8] = [8, 0, 0, 0, 0, 4, 0, 2];
+
+        // `Both` (the default) verifies on receive, so a bad checksum is rejected
+        assert!(icmp::Packet::parse_with_caps(
+            &BYTES[..],
+            &ipv4::ChecksumCapabilities::default()
+        ).is_err());
+
+        // `None` skips verification, so the same bytes are accepted as-is
+        let caps = ipv4::ChecksumCapabilities { icmp: ipv4::Checksum::None };
+        let packet = icmp::Packet::parse_with_caps(&BYTES[..], &caps).unwrap();
+        assert_eq!(packet.get_checksum(), 0);
+
+        // `Rx` only verifies on receive; it does not compute on transmit, so the checksum is
+        // left zeroed instead of being filled in
+        let mut array = BYTES;
+        let mut packet: icmp::Packet<_, Unknown, Invalid> =
+            unsafe { icmp::Packet::unchecked(&mut array[..]) };
+        packet.set_type(icmp::Type::EchoRequest);
+
+        let caps = ipv4::ChecksumCapabilities { icmp: ipv4::Checksum::Rx };
+        assert_eq!(packet.update_checksum_with_caps(&caps).get_checksum(), 0);
+    }
+
+    #[test]
+    fn timestamp_truncated() {
+        // type = Timestamp, code = 0, checksum is correct for these 8 bytes, but a real
+        // Timestamp header needs 20 bytes (up to TRANSMIT_TS); both the downcast and the
+        // `Repr::parse` path must reject this instead of indexing past the end of the buffer
+        const BYTES: [u8; 8] = [13, 0, 242, 255, 0, 0, 0, 0];
+
+        let packet = icmp::Packet::parse(&BYTES[..]).unwrap();
+
+        assert!(packet.clone().downcast::<icmp::Timestamp>().is_err());
+
+        match icmp::Repr::parse(&packet) {
+            Err(icmp::Error::Malformed) => {}
+            other => panic!("expected Err(Malformed), got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,52 @@
+//! IPv4: Internet Protocol version 4
+//!
+//! NOTE this file only carries the checksum capability types of the `ipv4` module; the rest of
+//! the module that `icmp` uses (`Packet`, `Addr`, `verify_checksum`, `compute_checksum`) is not
+//! part of this snapshot.
+
+/// Per-protocol checksum capabilities, e.g. to skip checksum work already done by NIC offloading
+///
+/// This lives next to the other checksum helpers (`verify_checksum`, `compute_checksum`) so
+/// `ether`, `ipv4` and `icmp` can all share a single definition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChecksumCapabilities {
+    /// ICMP checksum capability
+    pub icmp: Checksum,
+}
+
+impl Default for ChecksumCapabilities {
+    fn default() -> Self {
+        ChecksumCapabilities {
+            icmp: Checksum::Both,
+        }
+    }
+}
+
+/// Whether to verify a checksum on receive (Rx) and/or compute one on transmit (Tx)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Checksum {
+    /// Verify on receive and compute on transmit; the safe default
+    Both,
+    /// Only verify on receive
+    Rx,
+    /// Only compute on transmit
+    Tx,
+    /// Neither verify nor compute a checksum
+    None,
+}
+
+impl Checksum {
+    pub(crate) fn verify(&self) -> bool {
+        match *self {
+            Checksum::Both | Checksum::Rx => true,
+            Checksum::Tx | Checksum::None => false,
+        }
+    }
+
+    pub(crate) fn compute(&self) -> bool {
+        match *self {
+            Checksum::Both | Checksum::Tx => true,
+            Checksum::Rx | Checksum::None => false,
+        }
+    }
+}